@@ -2,6 +2,9 @@ use colored::Colorize;
 use regex::{Regex, RegexBuilder};
 use std::fs::File;
 use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
 use std::{error::Error, io::stdin, io::stdout};
 use walkdir::WalkDir;
 
@@ -19,7 +22,14 @@ pub fn grep<O: Write>(args: Args, writer: &mut O) -> Result<(), Box<dyn Error>>
 }
 
 fn create_regex(args: &Args) -> Regex {
-    match RegexBuilder::new(&args.query)
+    // with -x a line matches only when the whole line is the match, so anchor
+    // the user's pattern between the line boundaries
+    let pattern = if args.whole_line {
+        format!("^(?:{})$", args.query)
+    } else {
+        args.query.clone()
+    };
+    match RegexBuilder::new(&pattern)
         .case_insensitive(args.insensitive)
         .build()
     {
@@ -41,17 +51,127 @@ fn from_stdin<I: BufRead, O: Write>(
     re: &Regex,
 ) -> Result<(), Box<dyn Error>> {
     let lines = io.input.lines();
-    for line in lines.enumerate() {
-        if let (mut linenumber, Ok(l)) = line {
-            linenumber += 1;
-            if let Some(output) = handle_line(&l, linenumber, &re, &args) {
-                write!(io.output, "{}", output).unwrap();
+    if args.count {
+        let mut count = 0;
+        for line in lines {
+            if let Ok(l) = line {
+                if line_matches(&l, re, &args) {
+                    count += 1;
+                }
             }
         }
+        writeln!(io.output, "{}", count).unwrap();
+        return Ok(());
     }
+    let numbered = lines.enumerate().filter_map(|(i, l)| l.ok().map(|s| (i + 1, s)));
+    stream_with_context(numbered, re, &args, io.output, None);
     Ok(())
 }
 
+// decide whether a line counts as a match, honoring -v invert semantics;
+// case-insensitivity already lives in the compiled regex
+fn line_matches(line: &str, re: &Regex, args: &Args) -> bool {
+    let matched = re.find(line).is_some();
+    if args.invert {
+        !matched
+    } else {
+        matched
+    }
+}
+
+// resolve the requested context width; -C sets both sides, otherwise -A/-B apply
+fn effective_context(args: &Args) -> (usize, usize) {
+    if args.context > 0 {
+        (args.context, args.context)
+    } else {
+        (args.before_context, args.after_context)
+    }
+}
+
+// render a context line (no colorizing); context uses a `-` separator after the
+// line number, mirroring grep's distinction from matching `:` lines
+fn render_context_line(linenumber: usize, line: &str, args: &Args) -> String {
+    let mut s = String::new();
+    if args.linenumber {
+        s.push_str(&format!("{}-", linenumber));
+    }
+    s.push_str(line);
+    s.push('\n');
+    s
+}
+
+// print a file header lazily, at most once, before the first emitted line
+fn write_header<O: Write>(writer: &mut O, header: Option<&str>, printed: &mut bool) {
+    if let Some(name) = header {
+        if !*printed {
+            writeln!(writer, "{}", name.purple()).expect("ERROR: could not write to STDOUT");
+            *printed = true;
+        }
+    }
+}
+
+// stream matching lines together with their leading/trailing context. A ring
+// buffer holds the last `before` lines; `after` trailing lines are owed after
+// each match. Non-adjacent context groups are separated with `--` and
+// overlapping regions merge instead of reprinting lines.
+fn stream_with_context<I: Iterator<Item = (usize, String)>, O: Write>(
+    lines: I,
+    re: &Regex,
+    args: &Args,
+    writer: &mut O,
+    header: Option<&str>,
+) {
+    let (before, after) = effective_context(args);
+    let context_enabled = before > 0 || after > 0;
+    let mut ring: std::collections::VecDeque<(usize, String)> = std::collections::VecDeque::new();
+    let mut after_owed = 0;
+    let mut last_printed = 0;
+    let mut any_printed = false;
+    let mut header_printed = false;
+
+    for (linenumber, l) in lines {
+        if line_matches(&l, re, args) {
+            // first line we are about to emit: a buffered context line or the match itself
+            let first = ring
+                .iter()
+                .map(|(n, _)| *n)
+                .find(|n| *n > last_printed)
+                .unwrap_or(linenumber);
+            if context_enabled && any_printed && first > last_printed + 1 {
+                write_header(writer, header, &mut header_printed);
+                writeln!(writer, "--").expect("ERROR: could not write to STDOUT");
+            }
+            // flush the buffered preceding context
+            for (n, text) in ring.drain(..) {
+                if n > last_printed {
+                    write_header(writer, header, &mut header_printed);
+                    write!(writer, "{}", render_context_line(n, &text, args)).unwrap();
+                    last_printed = n;
+                }
+            }
+            // emit the selected line itself (handle_line honors -v/-n/color; the
+            // -H filename is the header written above)
+            if let Some(output) = handle_line(&l, linenumber, re, args) {
+                write_header(writer, header, &mut header_printed);
+                write!(writer, "{}", output).unwrap();
+            }
+            last_printed = linenumber;
+            any_printed = true;
+            after_owed = after;
+        } else if after_owed > 0 {
+            write_header(writer, header, &mut header_printed);
+            write!(writer, "{}", render_context_line(linenumber, &l, args)).unwrap();
+            last_printed = linenumber;
+            after_owed -= 1;
+        } else if before > 0 {
+            ring.push_back((linenumber, l));
+            if ring.len() > before {
+                ring.pop_front();
+            }
+        }
+    }
+}
+
 fn from_files<O: Write>(args: Args, re: &Regex, writer: &mut O) -> Result<(), Box<dyn Error>> {
     if args.recursive {
         // do recursive search only for a single directory
@@ -59,13 +179,69 @@ fn from_files<O: Write>(args: Args, re: &Regex, writer: &mut O) -> Result<(), Bo
             panic!("Recursive Search only works for a single directory");
         }
         let dir = &args.filenames[0];
+        let root = Path::new(dir);
+        let (positives, negatives) = compile_globs(&args.glob);
+        let ignore_rules = if args.no_ignore {
+            Vec::new()
+        } else {
+            collect_ignore_rules(root)
+        };
         let walker = WalkDir::new(dir).into_iter();
-        for entry in walker.into_iter().filter_map(|e| e.ok()) {
-            if entry.path().is_file() {
-                let filename = entry.path().to_str().expect("Invalid Path or Filename");
-                handle_file(filename, &args, &re, writer);
-            }
+        let mut paths: Vec<String> = walker
+            .filter_map(|e| e.ok())
+            .filter(|entry| entry.path().is_file())
+            .filter(|entry| {
+                let rel = entry.path().strip_prefix(root).unwrap_or(entry.path());
+                let rel = rel.to_str().unwrap_or("");
+                // skip dotfiles unless --hidden, and gitignored paths unless --no-ignore
+                if !args.hidden && is_hidden(rel) {
+                    return false;
+                }
+                !gitignore_ignored(entry.path(), &ignore_rules)
+            })
+            .map(|entry| {
+                entry
+                    .path()
+                    .to_str()
+                    .expect("Invalid Path or Filename")
+                    .to_string()
+            })
+            .filter(|path| path_selected(path, &positives, &negatives))
+            .collect();
+        // flush in sorted order when asked, otherwise keep discovery order
+        if args.sort {
+            paths.sort();
         }
+
+        // distribute handle_file work across a worker pool; each worker renders
+        // into an owned buffer so writes never interleave. Results are tagged
+        // with their position and reassembled in order before flushing.
+        let nthreads = args
+            .threads
+            .unwrap_or_else(|| thread::available_parallelism().map(|n| n.get()).unwrap_or(1))
+            .max(1);
+        let chunk_size = paths.len().div_ceil(nthreads).max(1);
+        let indexed: Vec<(usize, &String)> = paths.iter().enumerate().collect();
+        let (tx, rx) = mpsc::channel();
+        thread::scope(|s| {
+            for chunk in indexed.chunks(chunk_size) {
+                let tx = tx.clone();
+                let args = &args;
+                s.spawn(move || {
+                    for (idx, path) in chunk {
+                        let mut buf: Vec<u8> = Vec::new();
+                        handle_file(path, args, re, &mut buf);
+                        tx.send((*idx, buf)).expect("ERROR: could not queue result");
+                    }
+                });
+            }
+            drop(tx);
+            let mut collected: Vec<(usize, Vec<u8>)> = rx.iter().collect();
+            collected.sort_by_key(|(idx, _)| *idx);
+            for (_, buf) in collected {
+                writer.write_all(&buf).expect("ERROR: could not write to STDOUT");
+            }
+        });
         writeln!(writer, "").expect("ERROR: could not write to STDOUT "); // newline delimiter for every file
     } else {
         // do search for the given list of files
@@ -78,26 +254,263 @@ fn from_files<O: Write>(args: Args, re: &Regex, writer: &mut O) -> Result<(), Bo
     Ok(())
 }
 
+// split the repeatable --glob values into positive (include) and negated
+// (exclude, leading `!`) patterns, each compiled to an anchored regex
+fn compile_globs(globs: &[String]) -> (Vec<Regex>, Vec<Regex>) {
+    let mut positives = Vec::new();
+    let mut negatives = Vec::new();
+    for g in globs {
+        if let Some(stripped) = g.strip_prefix('!') {
+            negatives.push(glob_to_regex(stripped));
+        } else {
+            positives.push(glob_to_regex(g));
+        }
+    }
+    (positives, negatives)
+}
+
+fn glob_to_regex(glob: &str) -> Regex {
+    let mut pattern = String::from("^");
+    for ch in glob.chars() {
+        match ch {
+            '*' => pattern.push_str(".*"),
+            '?' => pattern.push('.'),
+            // escape every other regex metacharacter so the glob stays literal;
+            // otherwise patterns like `*.{js,ts}` or `a+b` are mis-parsed as regex
+            c if is_regex_meta(c) => {
+                pattern.push('\\');
+                pattern.push(c);
+            }
+            c => pattern.push(c),
+        }
+    }
+    pattern.push('$');
+    match Regex::new(&pattern) {
+        Ok(re) => re,
+        Err(e) => panic!("Error parsing given glob: {}", e),
+    }
+}
+
+// the set of characters the regex engine treats as special; mirrors
+// `regex::escape` so translated globs match path text literally
+fn is_regex_meta(c: char) -> bool {
+    matches!(
+        c,
+        '\\' | '.'
+            | '+'
+            | '*'
+            | '?'
+            | '('
+            | ')'
+            | '|'
+            | '['
+            | ']'
+            | '{'
+            | '}'
+            | '^'
+            | '$'
+            | '#'
+            | '&'
+            | '-'
+            | '~'
+    )
+}
+
+// a path is searched if it matches at least one positive glob (or there are
+// none) and matches no negated glob
+fn path_selected(path: &str, positives: &[Regex], negatives: &[Regex]) -> bool {
+    if negatives.iter().any(|re| re.is_match(path)) {
+        return false;
+    }
+    positives.is_empty() || positives.iter().any(|re| re.is_match(path))
+}
+
+// a single parsed `.gitignore` line, remembering the directory it lived in so
+// relative matching stays anchored to that level
+struct IgnoreRule {
+    regex: Regex,
+    negated: bool,
+    dir_only: bool,
+    anchored: bool,
+    has_slash: bool,
+    base: PathBuf,
+}
+
+// gather every `.gitignore` under the root, shallowest first, so that deeper
+// (closer) rules are evaluated last and therefore override their parents
+fn collect_ignore_rules(root: &Path) -> Vec<IgnoreRule> {
+    let mut files: Vec<PathBuf> = WalkDir::new(root)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_name() == ".gitignore")
+        .map(|e| e.path().to_path_buf())
+        .collect();
+    files.sort_by_key(|p| p.components().count());
+
+    let mut rules = Vec::new();
+    for file in files {
+        let base = match file.parent() {
+            Some(p) => p.to_path_buf(),
+            None => continue,
+        };
+        if let Ok(content) = std::fs::read_to_string(&file) {
+            for line in content.lines() {
+                let line = line.trim();
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+                rules.push(parse_ignore_rule(line, &base));
+            }
+        }
+    }
+    rules
+}
+
+fn parse_ignore_rule(line: &str, base: &Path) -> IgnoreRule {
+    let mut pat = line;
+    let negated = pat.starts_with('!');
+    if negated {
+        pat = &pat[1..];
+    }
+    let dir_only = pat.ends_with('/');
+    let pat = pat.trim_end_matches('/');
+    let anchored = pat.starts_with('/');
+    let pat = pat.trim_start_matches('/');
+    let has_slash = pat.contains('/');
+    IgnoreRule {
+        regex: glob_to_regex(pat),
+        negated,
+        dir_only,
+        anchored,
+        has_slash,
+        base: base.to_path_buf(),
+    }
+}
+
+// does `rel` (a path relative to the rule's base) match this rule? Anchored or
+// slash-bearing patterns match the whole relative path (or an ancestor, so a
+// matched directory ignores its whole subtree); plain patterns match any single
+// path component, restricted to directory components for `dir/` rules.
+fn rule_matches(rule: &IgnoreRule, rel: &str) -> bool {
+    let components: Vec<&str> = rel.split('/').collect();
+    if rule.anchored || rule.has_slash {
+        if rule.regex.is_match(rel) {
+            return true;
+        }
+        for i in 1..components.len() {
+            let prefix = components[..i].join("/");
+            if rule.regex.is_match(&prefix) {
+                return true;
+            }
+        }
+        false
+    } else {
+        let end = if rule.dir_only {
+            components.len().saturating_sub(1)
+        } else {
+            components.len()
+        };
+        components[..end].iter().any(|c| rule.regex.is_match(c))
+    }
+}
+
+// evaluate the accumulated rules in order; the last matching rule wins, so a
+// negation (`!pattern`) can re-include a path excluded by an earlier rule
+fn gitignore_ignored(path: &Path, rules: &[IgnoreRule]) -> bool {
+    let mut ignored = false;
+    for rule in rules {
+        if let Ok(rel) = path.strip_prefix(&rule.base) {
+            if let Some(rel) = rel.to_str() {
+                if rule_matches(rule, rel) {
+                    ignored = !rule.negated;
+                }
+            }
+        }
+    }
+    ignored
+}
+
+// a path is hidden if any of its components is a dotfile/dotdir
+fn is_hidden(rel: &str) -> bool {
+    rel.split('/')
+        .any(|c| c.starts_with('.') && c != "." && c != "..")
+}
+
 fn handle_file<O: Write>(filename: &str, args: &Args, re: &Regex, writer: &mut O) {
-    let mut found = false;
     let file = File::open(filename).expect("ERROR, file cannot be opened");
-    let lines = io::BufReader::new(file).lines();
-    for line in lines.enumerate() {
-        if let (mut linenumber, Ok(l)) = line {
-            linenumber += 1;
-            if let Some(output) = handle_line(&l, linenumber, &re, &args) {
-                if found == false && args.names {
-                    writeln!(writer, "{}", filename.purple())
-                        .expect("ERROR: could not write to STDOUT");
-                    found = true;
+    if args.count {
+        let mut count = 0;
+        for line in io::BufReader::new(file).lines() {
+            if let Ok(l) = line {
+                if line_matches(&l, re, args) {
+                    count += 1;
                 }
-                write!(writer, "{}", output).unwrap();
             }
         }
+        if args.names {
+            writeln!(writer, "{}:{}", filename, count).expect("ERROR: could not write to STDOUT");
+        } else {
+            writeln!(writer, "{}", count).expect("ERROR: could not write to STDOUT");
+        }
+        return;
     }
+    let numbered = io::BufReader::new(file)
+        .lines()
+        .enumerate()
+        .filter_map(|(i, l)| l.ok().map(|s| (i + 1, s)));
+    let header = if args.names { Some(filename) } else { None };
+    stream_with_context(numbered, re, args, writer, header);
 }
 
 fn handle_line(line: &str, linenumber: usize, re: &Regex, args: &Args) -> Option<String> {
+    // with -v we emit only the lines that do NOT match; colorizing is
+    // meaningless here so the raw line is returned uncolored (prefixed with its
+    // line number under -n)
+    if args.invert {
+        if re.find(line).is_none() {
+            let mut result = String::new();
+            if args.linenumber {
+                result.push_str(&format!("{}:", linenumber));
+            }
+            result.push_str(&format!("{}\n", line));
+            return Some(result);
+        }
+        return None;
+    }
+
+    // with --replace each match is rewritten through the template (supporting
+    // $1 / ${name} capture references) and the substituted text is printed
+    if let Some(template) = &args.replace {
+        let mut offset = 0;
+        let mut found = false;
+        let mut result = String::new();
+        for (i, caps) in re.captures_iter(line).enumerate() {
+            found = true;
+            let m = caps.get(0).unwrap();
+
+            if i == 0 && args.linenumber {
+                result.push_str(&format!("{}:", linenumber));
+            }
+            result.push_str(&line[offset..m.start()]);
+
+            let mut replaced = String::new();
+            caps.expand(template, &mut replaced);
+            if args.color {
+                result.push_str(&format!("{}", replaced.bold().red()));
+            } else {
+                result.push_str(&replaced);
+            }
+
+            offset = m.end();
+        }
+
+        if found {
+            result.push_str(&format!("{}\n", &line[offset..]));
+            return Some(result);
+        }
+        return None;
+    }
+
     let matches = re.find_iter(line);
     let mut offset = 0;
     let mut found = false;
@@ -152,13 +565,61 @@ pub struct Args {
     linenumber: bool,
 
     /// enable highlighting a match
-    #[clap(short = 'c', long, value_parser)]
+    #[clap(long, value_parser)]
     color: bool,
 
+    /// print only a count of matching lines per source
+    #[clap(short = 'c', long, value_parser)]
+    count: bool,
+
+    /// restrict recursive search to matching paths (repeatable; prefix with ! to exclude)
+    #[clap(long, value_parser)]
+    glob: Vec<String>,
+
+    /// print NUM lines of leading context before matches
+    #[clap(short = 'B', long = "before-context", value_parser, default_value_t = 0)]
+    before_context: usize,
+
+    /// print NUM lines of trailing context after matches
+    #[clap(short = 'A', long = "after-context", value_parser, default_value_t = 0)]
+    after_context: usize,
+
+    /// print NUM lines of context around matches
+    #[clap(short = 'C', long = "context", value_parser, default_value_t = 0)]
+    context: usize,
+
+    /// number of worker threads for recursive search (defaults to CPU count)
+    #[clap(long, value_parser)]
+    threads: Option<usize>,
+
+    /// sort recursive results by path instead of using discovery order
+    #[clap(long, value_parser)]
+    sort: bool,
+
+    /// include hidden dotfiles and dotdirs in recursive search
+    #[clap(long, value_parser)]
+    hidden: bool,
+
+    /// disable .gitignore handling during recursive search
+    #[clap(long = "no-ignore", value_parser)]
+    no_ignore: bool,
+
     /// enable recursive search in directories
-    #[clap(short = 'r', long, value_parser)]
+    #[clap(short = 'R', long, value_parser)]
     recursive: bool,
 
+    /// rewrite each match through the given replacement template ($1, ${name})
+    #[clap(short = 'r', long, value_parser)]
+    replace: Option<String>,
+
+    /// invert the match, selecting non-matching lines
+    #[clap(short = 'v', long, value_parser)]
+    invert: bool,
+
+    /// select only lines where the whole line is the match
+    #[clap(short = 'x', long = "line-regexp", value_parser)]
+    whole_line: bool,
+
     /// list of filenames to search in
     #[clap(value_parser)]
     filenames: Vec<String>,
@@ -207,6 +668,18 @@ foo foo FOO",
                 linenumber: false,
                 color: false,
                 recursive: true,
+                invert: false,
+                whole_line: false,
+                count: false,
+                glob: vec![],
+                before_context: 0,
+                after_context: 0,
+                context: 0,
+                threads: Some(1),
+                sort: false,
+                replace: None,
+                hidden: false,
+                no_ignore: false,
             };
 
             let mut v = Vec::new();
@@ -222,6 +695,217 @@ foo foo FOO",
         Ok(())
     }
 
+    #[test]
+    fn test_tmp_dir_recursive_with_glob() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = assert_fs::TempDir::new().unwrap();
+        if let Some(dirname) = dir.path().to_str() {
+            let file1 = dir.child("foo.txt");
+            let file2 = dir.child("bar.txt");
+            file1.write_str("foo bar\nbaz Foo").unwrap();
+            file2.write_str("foo foo FOO").unwrap();
+
+            let args = Args {
+                insensitive: true,
+                query: "foo".to_string(),
+                filenames: vec![dirname.to_string()],
+                names: false,
+                linenumber: false,
+                color: false,
+                recursive: true,
+                invert: false,
+                whole_line: false,
+                count: false,
+                glob: vec!["*foo.txt".to_string()],
+                before_context: 0,
+                after_context: 0,
+                context: 0,
+                threads: Some(1),
+                sort: false,
+                replace: None,
+                hidden: false,
+                no_ignore: false,
+            };
+
+            let mut v = Vec::new();
+            let _ = grep(args, &mut v);
+
+            // only foo.txt is searched, bar.txt is filtered out by the glob
+            let actual = String::from_utf8(v).expect("Not UTF-8");
+            let expected = "foo bar\nbaz Foo\n\n";
+            assert_eq!(expected, actual);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_tmp_dir_recursive_glob_with_metachars() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = assert_fs::TempDir::new().unwrap();
+        if let Some(dirname) = dir.path().to_str() {
+            // a filename carrying regex metacharacters must be matched literally
+            let file1 = dir.child("a{b.txt");
+            let file2 = dir.child("c.txt");
+            file1.write_str("foo one").unwrap();
+            file2.write_str("foo two").unwrap();
+
+            let args = Args {
+                insensitive: false,
+                query: "foo".to_string(),
+                filenames: vec![dirname.to_string()],
+                names: false,
+                linenumber: false,
+                color: false,
+                recursive: true,
+                invert: false,
+                whole_line: false,
+                count: false,
+                glob: vec!["*a{b.txt".to_string()],
+                before_context: 0,
+                after_context: 0,
+                context: 0,
+                threads: Some(1),
+                sort: true,
+                replace: None,
+                hidden: false,
+                no_ignore: false,
+            };
+
+            let mut v = Vec::new();
+            let _ = grep(args, &mut v);
+
+            // the `{` is escaped rather than parsed as a regex repetition
+            let actual = String::from_utf8(v).expect("Not UTF-8");
+            let expected = "foo one\n\n";
+            assert_eq!(expected, actual);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_tmp_dir_recursive_sorted() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = assert_fs::TempDir::new().unwrap();
+        if let Some(dirname) = dir.path().to_str() {
+            let file_a = dir.child("a.txt");
+            let file_b = dir.child("b.txt");
+            file_a.write_str("foo a").unwrap();
+            file_b.write_str("foo b").unwrap();
+
+            let args = Args {
+                insensitive: false,
+                query: "foo".to_string(),
+                filenames: vec![dirname.to_string()],
+                names: false,
+                linenumber: false,
+                color: false,
+                recursive: true,
+                invert: false,
+                whole_line: false,
+                count: false,
+                glob: vec![],
+                before_context: 0,
+                after_context: 0,
+                context: 0,
+                threads: Some(2),
+                sort: true,
+                replace: None,
+                hidden: false,
+                no_ignore: false,
+            };
+
+            let mut v = Vec::new();
+            let _ = grep(args, &mut v);
+
+            // --sort forces alphabetical path order regardless of thread count
+            let actual = String::from_utf8(v).expect("Not UTF-8");
+            let expected = "foo a\nfoo b\n\n";
+            assert_eq!(expected, actual);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_tmp_dir_recursive_respects_gitignore() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = assert_fs::TempDir::new().unwrap();
+        if let Some(dirname) = dir.path().to_str() {
+            dir.child(".gitignore").write_str("*.log\n").unwrap();
+            dir.child("app.log").write_str("foo here").unwrap();
+            dir.child("notes.txt").write_str("foo here").unwrap();
+
+            let args = Args {
+                insensitive: false,
+                query: "foo".to_string(),
+                filenames: vec![dirname.to_string()],
+                names: false,
+                linenumber: false,
+                color: false,
+                recursive: true,
+                invert: false,
+                whole_line: false,
+                count: false,
+                glob: vec![],
+                before_context: 0,
+                after_context: 0,
+                context: 0,
+                threads: Some(1),
+                sort: true,
+                replace: None,
+                hidden: false,
+                no_ignore: false,
+            };
+
+            let mut v = Vec::new();
+            let _ = grep(args, &mut v);
+
+            // app.log is ignored by the .gitignore rule, the dotfile is hidden
+            let actual = String::from_utf8(v).expect("Not UTF-8");
+            let expected = "foo here\n\n";
+            assert_eq!(expected, actual);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_tmp_dir_recursive_gitignore_literal_rule() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = assert_fs::TempDir::new().unwrap();
+        if let Some(dirname) = dir.path().to_str() {
+            // a rule with regex metacharacters must match the path literally and
+            // not panic the walk
+            dir.child(".gitignore").write_str("a+b.txt\n").unwrap();
+            dir.child("a+b.txt").write_str("foo ignored").unwrap();
+            dir.child("ab.txt").write_str("foo kept").unwrap();
+
+            let args = Args {
+                insensitive: false,
+                query: "foo".to_string(),
+                filenames: vec![dirname.to_string()],
+                names: false,
+                linenumber: false,
+                color: false,
+                recursive: true,
+                invert: false,
+                whole_line: false,
+                count: false,
+                glob: vec![],
+                before_context: 0,
+                after_context: 0,
+                context: 0,
+                threads: Some(1),
+                sort: true,
+                replace: None,
+                hidden: false,
+                no_ignore: false,
+            };
+
+            let mut v = Vec::new();
+            let _ = grep(args, &mut v);
+
+            // only a+b.txt is ignored; ab.txt is not matched by the literal rule
+            let actual = String::from_utf8(v).expect("Not UTF-8");
+            let expected = "foo kept\n\n";
+            assert_eq!(expected, actual);
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_tmp_files_with_names_no_color() -> Result<(), Box<dyn std::error::Error>> {
         let poem = assert_fs::NamedTempFile::new("poem.txt")?;
@@ -246,6 +930,18 @@ To an admiring bog!
                 linenumber: true,
                 color: false,
                 recursive: false,
+                invert: false,
+                whole_line: false,
+                count: false,
+                glob: vec![],
+                before_context: 0,
+                after_context: 0,
+                context: 0,
+                threads: Some(1),
+                sort: false,
+                replace: None,
+                hidden: false,
+                no_ignore: false,
             };
 
             let mut v = Vec::new();
@@ -272,6 +968,13 @@ To an admiring bog!
         insensitive: bool,
         linenumber: bool,
         color: bool,
+        invert: bool,
+        whole_line: bool,
+        count: bool,
+        before_context: usize,
+        after_context: usize,
+        context: usize,
+        replace: Option<String>,
         expected: String,
     }
 
@@ -294,6 +997,18 @@ To an admiring bog!
                         linenumber: c.linenumber,
                         color: c.color,
                         recursive: false,
+                        invert: c.invert,
+                        whole_line: c.whole_line,
+                        count: c.count,
+                        glob: vec![],
+                        before_context: c.before_context,
+                        after_context: c.after_context,
+                        context: c.context,
+                        threads: Some(1),
+                        sort: false,
+                        replace: c.replace.clone(),
+                        hidden: false,
+                        no_ignore: false,
                     };
                     let re = create_regex(&args);
                     from_stdin(io, args, &re).unwrap();
@@ -317,6 +1032,13 @@ foo baz",
             insensitive: true,
             linenumber: false,
             color: false,
+            invert: false,
+            whole_line: false,
+            count: false,
+            before_context: 0,
+            after_context: 0,
+            context: 0,
+            replace: None,
             expected: "bar baz FOO\n"
                 .to_string(),
         },
@@ -331,6 +1053,13 @@ foo baz",
             insensitive: true,
             linenumber: false,
             color: true,
+            invert: false,
+            whole_line: false,
+            count: false,
+            before_context: 0,
+            after_context: 0,
+            context: 0,
+            replace: None,
             expected: "bar baz \u{1b}[1;31mFOO\u{1b}[0m\n"
                 .to_string(),
         },
@@ -345,6 +1074,13 @@ foo baz",
             insensitive: false,
             linenumber: false,
             color: false,
+            invert: false,
+            whole_line: false,
+            count: false,
+            before_context: 0,
+            after_context: 0,
+            context: 0,
+            replace: None,
             expected: "foo bar
 foo baz
 "
@@ -361,6 +1097,13 @@ foo baz",
             insensitive: false,
             linenumber: false,
             color: false,
+            invert: false,
+            whole_line: false,
+            count: false,
+            before_context: 0,
+            after_context: 0,
+            context: 0,
+            replace: None,
             expected: "bar baz FOO\n"
                 .to_string(),
         },
@@ -376,8 +1119,131 @@ foo baz",
             insensitive: false,
             linenumber: true,
             color: false,
+            invert: false,
+            whole_line: false,
+            count: false,
+            before_context: 0,
+            after_context: 0,
+            context: 0,
+            replace: None,
             expected: "1:foo bar
 4:foo baz
+"
+                .to_string(),
+        },
+
+        grep_match_inverted: Case {
+            testname: "match_inverted".to_string(),
+            input: b"foo bar
+bar baz
+bar baz FOO
+foo baz",
+            query: "foo".to_string(),
+            names: false,
+            insensitive: false,
+            linenumber: false,
+            color: false,
+            invert: true,
+            whole_line: false,
+            count: false,
+            before_context: 0,
+            after_context: 0,
+            context: 0,
+            replace: None,
+            expected: "bar baz
+bar baz FOO
+"
+                .to_string(),
+        },
+
+        grep_match_whole_line: Case {
+            testname: "match_whole_line".to_string(),
+            input: b"foo bar
+foo
+bar foo",
+            query: "foo".to_string(),
+            names: false,
+            insensitive: false,
+            linenumber: false,
+            color: false,
+            invert: false,
+            whole_line: true,
+            count: false,
+            before_context: 0,
+            after_context: 0,
+            context: 0,
+            replace: None,
+            expected: "foo\n"
+                .to_string(),
+        },
+
+        grep_match_count: Case {
+            testname: "match_count".to_string(),
+            input: b"foo bar
+bar baz
+bar baz FOO
+foo baz",
+            query: "foo".to_string(),
+            names: false,
+            insensitive: false,
+            linenumber: false,
+            color: false,
+            invert: false,
+            whole_line: false,
+            count: true,
+            before_context: 0,
+            after_context: 0,
+            context: 0,
+            replace: None,
+            expected: "2\n"
+                .to_string(),
+        },
+
+        grep_match_with_context: Case {
+            testname: "match_with_context".to_string(),
+            input: b"foo bar
+bar baz
+bar baz FOO
+foo baz",
+            query: "foo".to_string(),
+            names: false,
+            insensitive: false,
+            linenumber: false,
+            color: false,
+            invert: false,
+            whole_line: false,
+            count: false,
+            before_context: 0,
+            after_context: 0,
+            context: 1,
+            replace: None,
+            expected: "foo bar
+bar baz
+bar baz FOO
+foo baz
+"
+                .to_string(),
+        },
+
+        grep_match_replace: Case {
+            testname: "match_replace".to_string(),
+            input: b"foo bar
+bar baz
+foo baz",
+            query: "foo".to_string(),
+            names: false,
+            insensitive: false,
+            linenumber: false,
+            color: false,
+            invert: false,
+            whole_line: false,
+            count: false,
+            before_context: 0,
+            after_context: 0,
+            context: 0,
+            replace: Some("qux".to_string()),
+            expected: "qux bar
+qux baz
 "
                 .to_string(),
         },